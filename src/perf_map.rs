@@ -1,6 +1,7 @@
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
 use rand::Rng;
+use rand::SeedableRng;
 use std::collections::HashSet;
 use std::sync::Mutex;
 use std::sync::{
@@ -23,6 +24,11 @@ pub trait Collection: Send + Sync + 'static {
     type Handle: CollectionHandle;
     fn pin(&self) -> Self::Handle;
     fn prefill_complete(&self);
+
+    /// Forces any buffered writes to durable storage. In-memory collections can
+    /// leave this as the default no-op; disk-backed engines (LSM/B-tree) should
+    /// fsync so persistence cost is captured alongside op latency.
+    fn flush(&self) {}
 }
 
 /// A handle to a key-value collection.
@@ -32,11 +38,16 @@ pub trait Collection: Send + Sync + 'static {
 /// retrievals indeed return the right results.
 pub trait CollectionHandle {
     type Key: Clone + Send + Sync + FromU64;
-
-    fn get(&self, key: &Self::Key) -> bool;
-    fn insert(&self, key: Self::Key) -> bool;
+    /// The value stored for each key. Derived deterministically from the key's
+    /// index so a retrieval can be checked against the value we expect to find
+    /// (see the `verify` mode of [`SharedMapTestConfig`]).
+    type Value: Clone + Eq + FromU64 + ValueModifier;
+
+    /// Looks the key up, returning the stored value so callers can verify it.
+    fn get(&self, key: &Self::Key) -> Option<Self::Value>;
+    fn insert(&self, key: Self::Key, value: Self::Value) -> bool;
     fn remove(&self, key: &Self::Key) -> bool;
-    fn update(&self, key: &Self::Key) -> bool;
+    fn update(&self, key: &Self::Key, value: Self::Value) -> bool;
 }
 
 pub trait ValueModifier {
@@ -85,16 +96,30 @@ where
     TK: Send + Sync + Clone + FromU64,
 {
     pub fn new(total_keys: usize) -> Self {
-        let mut rng = rand::thread_rng();
+        Self::from_rng(total_keys, &mut rand::thread_rng())
+    }
+
+    /// Like [`Keys::new`] but drawn from a fixed seed, so the exact key set is
+    /// reproducible across runs.
+    pub fn seeded(total_keys: usize, seed: u64) -> Self {
+        Self::from_rng(total_keys, &mut StdRng::seed_from_u64(seed))
+    }
+
+    fn from_rng<R: Rng>(total_keys: usize, rng: &mut R) -> Self {
         let mut unique_set = HashSet::new();
 
         while unique_set.len() < total_keys {
             unique_set.insert(rng.gen::<u64>());
         }
 
+        // Sort before converting so the key ordering is a deterministic
+        // function of the drawn set, not of `HashSet`'s randomized iteration.
+        let mut raw: Vec<u64> = unique_set.into_iter().collect();
+        raw.sort_unstable();
+
         Self {
             allocated: Arc::new(AtomicUsize::new(0)),
-            keys: unique_set.into_iter().map(TK::from_u64).collect(),
+            keys: raw.into_iter().map(TK::from_u64).collect(),
         }
     }
 
@@ -107,6 +132,34 @@ where
         self.keys[i % allocated].clone()
     }
 
+    /// Returns the key at `index`, wrapped into the currently allocated range.
+    ///
+    /// The wrapping is the same as in `random`; `at` is the entry point for an
+    /// `index` already drawn from a distribution (e.g. by [`Sampler`]).
+    pub fn at(&self, index: usize) -> TK {
+        let allocated = self.allocated.load(Ordering::Relaxed);
+        self.keys[index % allocated].clone()
+    }
+
+    pub fn allocated(&self) -> usize {
+        self.allocated.load(Ordering::Relaxed)
+    }
+
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    /// Like [`Keys::alloc_n`], but also returns the starting index of the
+    /// allocated run so callers can map each key back to its deterministic value.
+    pub fn alloc_n_indexed(&self, count: usize) -> (usize, &[TK]) {
+        let i = self.allocated.fetch_add(count, Ordering::Relaxed);
+        (i, &self.keys[i..(i + count)])
+    }
+
     // too slow
     // pub fn alloc(&self) -> TK {
     //     let i = self.allocated.fetch_add(1, Ordering::Relaxed);
@@ -119,6 +172,184 @@ where
     }
 }
 
+/// Thread-to-core pinning policy applied before the start barrier.
+///
+/// Pinning workers before they begin keeps contention measurements stable and
+/// reproducible; on NUMA/hybrid machines the policy also decides whether
+/// cross-socket cache-coherence traffic shows up in the numbers. The list of
+/// cores is whatever [`core_affinity::get_core_ids`] reports, in its order.
+#[derive(Clone, Copy, Debug)]
+pub enum Affinity {
+    /// Let the OS scheduler place threads (the default, unpinned behaviour).
+    None,
+    /// Worker `n` pins to core `n % cores`, cycling across all cores.
+    RoundRobin,
+    /// Pack workers onto the lowest-numbered cores in consecutive groups of
+    /// `ceil(threads / cores)`: one worker per core while there are no more
+    /// workers than cores; beyond that each low core takes a full group and the
+    /// highest cores may get none. `core_affinity` reports no SMT/NUMA topology, so this is
+    /// plain index math, not true sibling- or node-aware packing.
+    Compact,
+    /// Spread workers across distinct cores first to expose cross-core costs.
+    Spread,
+}
+
+impl Default for Affinity {
+    fn default() -> Self {
+        Affinity::None
+    }
+}
+
+impl Affinity {
+    /// Builds the per-worker core assignment (an index into the core list, or
+    /// `None` to leave the worker unpinned) for `threads` workers over `cores`.
+    fn plan(self, threads: usize, cores: usize) -> Vec<Option<usize>> {
+        if cores == 0 {
+            return vec![None; threads];
+        }
+        (0..threads)
+            .map(|n| match self {
+                Affinity::None => None,
+                Affinity::RoundRobin => Some(n % cores),
+                Affinity::Compact => {
+                    let per_core = threads.div_ceil(cores).max(1);
+                    Some((n / per_core).min(cores - 1))
+                }
+                Affinity::Spread => {
+                    // Space the workers evenly across the core list so adjacent
+                    // workers land as far apart as the core count allows, even
+                    // when `threads` is close to `cores` (an integer stride
+                    // would floor to 1 and pack them onto consecutive cores).
+                    Some((n * cores) / threads.max(1) % cores)
+                }
+            })
+            .collect()
+    }
+}
+
+/// Key-access distribution used when drawing keys for read/update/remove ops.
+///
+/// `Uniform` picks every allocated key with equal probability. `Zipfian` skews
+/// access so a few hot keys dominate, matching YCSB and real-world map traffic.
+#[derive(Clone, Copy, Debug)]
+pub enum Distribution {
+    Uniform,
+    /// YCSB-style Zipfian. `theta` controls skew (higher == more skewed; 0.99 is
+    /// the YCSB default). With `scramble`, the produced index is mixed through
+    /// fmix64 before indexing so the hot keys are spread across the table rather
+    /// than clustered at low indices.
+    Zipfian { theta: f64, scramble: bool },
+}
+
+impl Default for Distribution {
+    fn default() -> Self {
+        Distribution::Uniform
+    }
+}
+
+impl Distribution {
+    /// The YCSB default Zipfian distribution (`theta = 0.99`), unscrambled.
+    pub fn zipfian() -> Self {
+        Distribution::Zipfian {
+            theta: 0.99,
+            scramble: false,
+        }
+    }
+}
+
+/// Precomputed YCSB Zipfian generator over `n` items.
+///
+/// The constants follow the YCSB `ZipfianGenerator`: `zetan` is the n-th
+/// generalized harmonic number, `alpha = 1/(1-theta)`, `zeta2 = 1 + 0.5^theta`,
+/// and `eta` ties the tail back to a uniform draw `u`.
+struct Zipf {
+    n: usize,
+    alpha: f64,
+    zetan: f64,
+    zeta2: f64,
+    eta: f64,
+    scramble: bool,
+}
+
+impl Zipf {
+    fn new(n: usize, theta: f64, scramble: bool) -> Self {
+        let nf = n as f64;
+        let zetan: f64 = (1..=n).map(|i| 1.0 / (i as f64).powf(theta)).sum();
+        let alpha = 1.0 / (1.0 - theta);
+        let zeta2 = 1.0 + 0.5f64.powf(theta);
+        let eta = (1.0 - (2.0 / nf).powf(1.0 - theta)) / (1.0 - zeta2 / zetan);
+        Self {
+            n,
+            alpha,
+            zetan,
+            zeta2,
+            eta,
+            scramble,
+        }
+    }
+
+    fn next(&self, u: f64) -> usize {
+        let uz = u * self.zetan;
+        let idx = if uz < 1.0 {
+            0
+        } else if uz < self.zeta2 {
+            1
+        } else {
+            (self.n as f64 * (self.eta * u - self.eta + 1.0).powf(self.alpha)) as usize
+        };
+        if self.scramble {
+            (fmix64(idx as u64) as usize) % self.n
+        } else {
+            idx
+        }
+    }
+}
+
+/// Fast 64-bit finalizer (the MurmurHash3 `fmix64` mixer), used to scatter
+/// Zipfian indices so hot keys land in distinct buckets.
+fn fmix64(mut k: u64) -> u64 {
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xff51afd7ed558ccd);
+    k ^= k >> 33;
+    k = k.wrapping_mul(0xc4ceb9fe1a85ec53);
+    k ^= k >> 33;
+    k
+}
+
+/// The value a key at `index` should hold after `mods` modifications, built by
+/// seeding from the index and applying [`ValueModifier::modify`] that many times.
+/// Because `modify` only ever moves a value forward, the expected value for a
+/// given `mods` is unambiguous.
+fn value_for<V: FromU64 + ValueModifier>(index: usize, mods: usize) -> V {
+    let mut v = V::from_u64(index as u64);
+    for _ in 0..mods {
+        v.modify();
+    }
+    v
+}
+
+/// Draws key indices according to a [`Distribution`], built once per worker.
+enum Sampler {
+    Uniform,
+    Zipf(Zipf),
+}
+
+impl Sampler {
+    fn new(dist: Distribution, n: usize) -> Self {
+        match dist {
+            Distribution::Uniform => Sampler::Uniform,
+            Distribution::Zipfian { theta, scramble } => Sampler::Zipf(Zipf::new(n, theta, scramble)),
+        }
+    }
+
+    fn sample<R: Rng>(&self, rng: &mut R, n: usize) -> usize {
+        match self {
+            Sampler::Uniform => rng.gen::<usize>() % n,
+            Sampler::Zipf(z) => z.next(rng.gen::<f64>()),
+        }
+    }
+}
+
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Operation {
     Read,
@@ -172,13 +403,23 @@ impl Mix {
 
     // Assuming 'Operation' enum is defined similarly to the previous examples
     pub fn to_ops(&self) -> Vec<Operation> {
+        self.to_ops_with(&mut rand::thread_rng())
+    }
+
+    /// Like [`Mix::to_ops`] but shuffled with a fixed seed, so the op list is
+    /// reproducible across runs.
+    pub fn to_ops_seeded(&self, seed: u64) -> Vec<Operation> {
+        self.to_ops_with(&mut StdRng::seed_from_u64(seed))
+    }
+
+    fn to_ops_with<R: Rng>(&self, rng: &mut R) -> Vec<Operation> {
         let mut list = Vec::with_capacity(100);
         list.extend(std::iter::repeat(Operation::Read).take(self.read as usize));
         list.extend(std::iter::repeat(Operation::Insert).take(self.insert as usize));
         list.extend(std::iter::repeat(Operation::Remove).take(self.remove as usize));
         list.extend(std::iter::repeat(Operation::Update).take(self.update as usize));
         list.extend(std::iter::repeat(Operation::Upsert).take(self.upsert as usize));
-        list.shuffle(&mut rand::thread_rng());
+        list.shuffle(rng);
         list
     }
 }
@@ -190,6 +431,29 @@ pub struct SharedMapTestConfig<'a> {
     pub prefill: usize,
     pub operations: &'a Vec<Operation>,
     pub keys_needed_per_thread: usize,
+    pub distribution: Distribution,
+    /// When set, each worker timestamps every operation so the run can report
+    /// p50/p99/p999/max. Leave off to keep the cheap aggregate-timing path.
+    pub record_latencies: bool,
+    /// When set, values are checked on every `get` against the value the key is
+    /// expected to hold, turning the harness into a concurrency stress tester.
+    /// The run panics with a diagnostic on the first mismatch.
+    pub verify: bool,
+    /// How worker threads are pinned to cores before the start barrier.
+    pub affinity: Affinity,
+    /// Seed for the workers' key draws. Each worker seeds its own stream as
+    /// `seed ^ thread_index`, so a fixed seed and thread count repeat each
+    /// worker's sequence of random draws. The key set and the op list come from
+    /// the caller and are not drawn from this seed (see [`Keys::seeded`] and
+    /// [`Mix::to_ops_seeded`]). Workers claim their insert ranges and read the
+    /// allocated key count at run time, so which insert keys a worker gets, and
+    /// the range its draws are reduced into, depend on the order in which the
+    /// workers start; the per-thread key sequence is not guaranteed to repeat.
+    pub seed: u64,
+    /// When set, each worker calls [`Collection::flush`] every this-many ops to
+    /// force durability mid-run; `None` never flushes during the op loop. A
+    /// value of `0` is invalid and treated as "never flush".
+    pub flush_every: Option<usize>,
 }
 fn run_ops<H: CollectionHandle>(
     dict: &H, // Assuming you have a ConcurrentDictionary type
@@ -197,41 +461,123 @@ fn run_ops<H: CollectionHandle>(
     op_mix: &[Operation],
     ops_per_thread: usize,
     keys_needed_per_thread: usize,
+    distribution: Distribution,
+    latencies: Option<&mut Vec<u64>>,
+    verify: Option<(&[AtomicUsize], &[AtomicUsize])>,
+    seed: u64,
+    flush_every: Option<usize>,
+    flush: impl Fn(),
 ) -> usize {
-    let mut rng = thread_rng();
+    let mut rng = StdRng::seed_from_u64(seed);
     let op_mix_count = op_mix.len();
     let mut total_success = 0;
-    let mut new_keys = keys.alloc_n(keys_needed_per_thread).iter().cycle();
+    let (ins_base, ins_keys) = keys.alloc_n_indexed(keys_needed_per_thread);
+    let mut ins_pos = 0usize;
+    let sampler = Sampler::new(distribution, keys.allocated().max(1));
+    // When recording, the caller hands us a vector already reserved to
+    // `ops_per_thread` so the per-op push never reallocates on the hot path.
+    let mut latencies = latencies;
 
     for i in 0..ops_per_thread {
         let op = op_mix[i % op_mix_count];
-        let r = rng.gen::<usize>(); // Generate a random usize
+        let n = keys.allocated().max(1);
+        let op_start = latencies.as_ref().map(|_| Instant::now());
         let success = match op {
-            Operation::Read => dict.get(&keys.random(r)),
-            Operation::Insert => dict.insert(new_keys.next().unwrap().clone()),
-            Operation::Remove => dict.remove(&keys.random(r)),
-            Operation::Update => {
-                dict.update(&keys.random(r))
-                // if let Some(existing_value) = dict.get(&keys.random(r)) {
-                //     dict.insert(keys.random(r), existing_value + 1).is_some()
-                // } else {
-                //     false
-                // }
+            Operation::Read => {
+                let idx = sampler.sample(&mut rng, n);
+                match verify {
+                    // Writes are monotonic, so the value a concurrent reader
+                    // observes corresponds to some modification count bounded by
+                    // the writes in flight around the lookup. A single counter
+                    // cannot bound both ends: `started` is bumped before the
+                    // store and `completed` after, so the smallest value that
+                    // could still be visible is `completed` as seen before the
+                    // `get`, and the largest is `started` as seen after it.
+                    // Accept any count in that window; anything outside is a bug.
+                    Some((started, completed)) => {
+                        let before = completed[idx].load(Ordering::Acquire);
+                        let got = dict.get(&keys.at(idx));
+                        let after = started[idx].load(Ordering::Acquire);
+                        if let Some(value) = &got {
+                            let ok = (before..=after)
+                                .any(|c| *value == value_for::<H::Value>(idx, c));
+                            assert!(
+                                ok,
+                                "verify: torn/lost value at key index {idx} \
+                                 (expected modification count in {before}..={after})"
+                            );
+                        }
+                        got.is_some()
+                    }
+                    None => dict.get(&keys.at(idx)).is_some(),
+                }
             }
-            Operation::Upsert => {
-                // Note: Rust's `insert` always returns the old value, even if the key didn't exist before
-                //let old_value = dict.insert(keys.random(r), 1);
-                //old_value.is_none() || old_value.unwrap() == 0
-                dict.update(&keys.random(r))
+            Operation::Insert => {
+                let local = ins_pos % ins_keys.len();
+                let idx = ins_base + local;
+                ins_pos += 1;
+                // Inserts write through the same claim/commit protocol as
+                // updates so a concurrent reader's window covers the value they
+                // land; `completed` only advances once the write has taken.
+                let mods = verify.map_or(0, |(started, _)| {
+                    started[idx].fetch_add(1, Ordering::AcqRel) + 1
+                });
+                let ret = dict.insert(ins_keys[local].clone(), value_for::<H::Value>(idx, mods));
+                if ret {
+                    if let Some((_, completed)) = verify {
+                        completed[idx].fetch_add(1, Ordering::AcqRel);
+                    }
+                }
+                ret
+            }
+            Operation::Remove => dict.remove(&keys.at(sampler.sample(&mut rng, n))),
+            Operation::Update | Operation::Upsert => {
+                let idx = sampler.sample(&mut rng, n);
+                // Claim the next count on `started` before the store so a reader
+                // that sees the new value can still bound it from above, then
+                // bump `completed` only once the store has actually taken so a
+                // reader that sees the old value is bounded from below. A failed
+                // update (absent key, nothing stored) must not advance
+                // `completed`, or the window would exclude the value still held.
+                let mods = verify.map_or(1, |(started, _)| {
+                    started[idx].fetch_add(1, Ordering::AcqRel) + 1
+                });
+                let ret = dict.update(&keys.at(idx), value_for::<H::Value>(idx, mods));
+                if ret {
+                    if let Some((_, completed)) = verify {
+                        completed[idx].fetch_add(1, Ordering::AcqRel);
+                    }
+                }
+                ret
             }
         };
 
+        if let (Some(start), Some(buf)) = (op_start, latencies.as_mut()) {
+            buf.push(start.elapsed().as_nanos() as u64);
+        }
+
+        if let Some(every) = flush_every {
+            if every != 0 && (i + 1) % every == 0 {
+                flush();
+            }
+        }
+
         total_success += if success { 0 } else { 1 };
     }
 
     total_success
 }
 
+/// Returns the value at the given percentile (`q` in `[0,1]`) of an already
+/// sorted slice of nanosecond samples, or `0.0` if the slice is empty.
+fn percentile(sorted: &[u64], q: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+    let rank = (q * (sorted.len() - 1) as f64).round() as usize;
+    sorted[rank.min(sorted.len() - 1)] as f64
+}
+
 pub fn run_shared_map_test<'a, H: Collection>(
     name: &'a str,
     collection: Arc<H>,
@@ -246,32 +592,66 @@ pub fn run_shared_map_test<'a, H: Collection>(
     let mut thread_handles = Vec::with_capacity(num_threads);
     let ops_per_thread = config.total_ops / num_threads;
     let results = Arc::new(Mutex::new(Vec::<Duration>::new()));
+    let samples = Arc::new(Mutex::new(Vec::<u64>::new()));
+
+    // Per-key modification counters backing verify mode; one slot per key in
+    // each array. `started` is bumped before a write claims its value and
+    // `completed` after it lands, giving readers a two-sided window.
+    let verify_counters: Option<(Arc<Vec<AtomicUsize>>, Arc<Vec<AtomicUsize>>)> = if config.verify {
+        let started = (0..keys.len()).map(|_| AtomicUsize::new(0)).collect();
+        let completed = (0..keys.len()).map(|_| AtomicUsize::new(0)).collect();
+        Some((Arc::new(started), Arc::new(completed)))
+    } else {
+        None
+    };
 
     keys.reset();
-    let mut new_keys = keys.alloc_n(config.prefill).iter().cycle();
+    let (prefill_base, prefill_keys) = keys.alloc_n_indexed(config.prefill);
+    // Time prefill together with its flush: warm-up and fsync amortization
+    // dominate write-heavy comparisons between volatile and persistent maps.
+    let prefill_start = Instant::now();
     let inserter = collection.pin();
-    for _ in 0..config.prefill {
-        inserter.insert(new_keys.next().unwrap().clone());
+    for local in 0..config.prefill {
+        let idx = prefill_base + local;
+        let key = prefill_keys[local].clone();
+        inserter.insert(key, value_for(idx, 0));
     }
+    collection.flush();
+    let prefill_nanos = prefill_start.elapsed().as_nanos() as f64;
 
     collection.prefill_complete();
 
-    // uncomment for core affinity
-    // affinity: let core_ids = get_core_ids().expect("Failed to get core IDs");
+    // Enumerate the cores once and compute each worker's target up front.
+    let core_ids = core_affinity::get_core_ids().unwrap_or_default();
+    let affinity_plan = config.affinity.plan(num_threads, core_ids.len());
 
-    for _ in 0..num_threads {
+    for n in 0..num_threads {
         let operations = config.operations.clone();
         let keys_needed_per_thread = config.keys_needed_per_thread;
+        let distribution = config.distribution;
+        let record_latencies = config.record_latencies;
+        let thread_seed = config.seed ^ n as u64;
+        let flush_every = config.flush_every;
+        let verify_counters = verify_counters.clone();
         let barrier = barrier.clone();
         let results_clone = results.clone();
+        let samples_clone = samples.clone();
         let collection = collection.clone();
         let keys = keys.clone();
-        // affinity: let core_id = core_ids[n % core_ids.len()];
-        // affinity: let core_id_usize = core_id.id as usize;
+        let core_id = affinity_plan[n].map(|i| core_ids[i]);
 
         let handle = thread::spawn(move || {
-            // affinity: set_thread_affinity(&[core_id_usize]).expect("Failed to set thread affinity");
+            if let Some(core_id) = core_id {
+                core_affinity::set_for_current(core_id);
+            }
             let dict = collection.pin();
+            // Reserve the per-thread sample buffer up front so the recording
+            // path never allocates inside the measured loop.
+            let mut local = if record_latencies {
+                Some(Vec::with_capacity(ops_per_thread))
+            } else {
+                None
+            };
             barrier.wait();
             let start_time = Instant::now();
             run_ops(
@@ -280,11 +660,21 @@ pub fn run_shared_map_test<'a, H: Collection>(
                 &operations,
                 ops_per_thread,
                 keys_needed_per_thread,
+                distribution,
+                local.as_mut(),
+                verify_counters
+                    .as_ref()
+                    .map(|(s, c)| (s.as_slice(), c.as_slice())),
+                thread_seed,
+                flush_every,
+                || collection.flush(),
             );
 
             let elapsed = start_time.elapsed();
-            let mut results = results_clone.lock().unwrap();
-            results.push(elapsed);
+            results_clone.lock().unwrap().push(elapsed);
+            if let Some(mut local) = local {
+                samples_clone.lock().unwrap().append(&mut local);
+            }
         });
 
         thread_handles.push(handle);
@@ -295,18 +685,249 @@ pub fn run_shared_map_test<'a, H: Collection>(
         handle.join().unwrap();
     }
 
+    // A final durability flush, measured separately from op latency.
+    let flush_start = Instant::now();
+    collection.flush();
+    let flush_nanos = flush_start.elapsed().as_nanos() as f64;
+
     let real_total_ops = ops_per_thread as u64 * num_threads as u64;
     let average_duration = calc_av_nanos(results, real_total_ops);
 
-    println!("avg: {:8.2} ns", average_duration);
+    // Merge the per-thread samples and derive tail percentiles. Without
+    // recording we fall back to the mean so the fields are always populated.
+    let (p50, p99, p999, max) = {
+        let mut merged = Arc::try_unwrap(samples)
+            .map(|m| m.into_inner().unwrap())
+            .unwrap_or_default();
+        if merged.is_empty() {
+            (average_duration, average_duration, average_duration, average_duration)
+        } else {
+            merged.sort_unstable();
+            (
+                percentile(&merged, 0.50),
+                percentile(&merged, 0.99),
+                percentile(&merged, 0.999),
+                *merged.last().unwrap() as f64,
+            )
+        }
+    };
+
+    if config.record_latencies {
+        println!(
+            "avg: {average_duration:8.2} ns  p50: {p50:.0}  p99: {p99:.0}  p999: {p999:.0}  max: {max:.0}"
+        );
+    } else {
+        println!("avg: {average_duration:8.2} ns");
+    }
 
     Measurement {
         name,
         latency: average_duration,
         thread_count: num_threads as u64,
+        p50,
+        p99,
+        p999,
+        max,
+        affinity: config.affinity,
+        prefill_nanos,
+        flush_nanos,
     }
 }
 
+/// Shared parameters for a [`run_sweep`], holding everything constant except
+/// the thread count and workload that the sweep varies.
+#[derive(Clone, Copy, Debug)]
+pub struct SweepParams {
+    pub total_ops: usize,
+    pub prefill: usize,
+    pub keys_needed_per_thread: usize,
+    pub distribution: Distribution,
+    pub record_latencies: bool,
+    pub verify: bool,
+    pub affinity: Affinity,
+    pub seed: u64,
+    pub flush_every: Option<usize>,
+}
+
+/// One cell of a sweep: a single `(name, workload, thread_count)` measurement.
+#[derive(Clone, Debug)]
+pub struct SweepEntry {
+    pub name: String,
+    pub workload: String,
+    pub thread_count: u64,
+    pub latency: f64,
+    pub p50: f64,
+    pub p99: f64,
+    pub p999: f64,
+    pub max: f64,
+}
+
+/// The full result set of a [`run_sweep`], serializable to CSV or JSON.
+#[derive(Clone, Debug, Default)]
+pub struct SweepResults {
+    pub entries: Vec<SweepEntry>,
+}
+
+/// Quotes a CSV field per RFC 4180: wrap in double quotes and double any inner
+/// quote. Always quoting keeps embedded commas and newlines from splitting the
+/// row regardless of the field's contents.
+fn csv_field(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        if c == '"' {
+            out.push('"');
+        }
+        out.push(c);
+    }
+    out.push('"');
+    out
+}
+
+/// Escapes a string as a JSON string literal (including the surrounding quotes),
+/// covering the control characters and backslash/quote that must be escaped.
+fn json_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len() + 2);
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+/// Renders a float as a JSON number, falling back to `null` for `NaN`/infinity
+/// since JSON has no representation for them.
+fn json_number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        String::from("null")
+    }
+}
+
+/// Renders a float as a CSV cell, leaving the cell empty for `NaN`/infinity so
+/// the column stays numeric rather than spilling `NaN`/`inf` literals.
+fn csv_number(x: f64) -> String {
+    if x.is_finite() {
+        x.to_string()
+    } else {
+        String::new()
+    }
+}
+
+impl SweepResults {
+    /// Renders the results as CSV, one row per `(name, workload, thread_count)`.
+    /// `name` and `workload` are quoted, so commas, quotes, and newlines in them
+    /// are preserved rather than corrupting the row layout, and non-finite
+    /// latencies render as empty cells to match the JSON serializer's `null`.
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("name,workload,thread_count,latency,p50,p99,p999,max\n");
+        for e in &self.entries {
+            out.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                csv_field(&e.name),
+                csv_field(&e.workload),
+                e.thread_count,
+                csv_number(e.latency),
+                csv_number(e.p50),
+                csv_number(e.p99),
+                csv_number(e.p999),
+                csv_number(e.max)
+            ));
+        }
+        out
+    }
+
+    /// Renders the results as a JSON array of objects. String fields are escaped
+    /// and non-finite latencies are emitted as `null` so the output always
+    /// parses as valid JSON.
+    pub fn to_json(&self) -> String {
+        let mut out = String::from("[");
+        for (i, e) in self.entries.iter().enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            out.push_str(&format!(
+                "{{\"name\":{},\"workload\":{},\"thread_count\":{},\
+                 \"latency\":{},\"p50\":{},\"p99\":{},\"p999\":{},\"max\":{}}}",
+                json_string(&e.name),
+                json_string(&e.workload),
+                e.thread_count,
+                json_number(e.latency),
+                json_number(e.p50),
+                json_number(e.p99),
+                json_number(e.p999),
+                json_number(e.max)
+            ));
+        }
+        out.push(']');
+        out
+    }
+}
+
+/// Runs the full cross-product of `thread_counts` and named `workloads` against
+/// the collection, holding total ops and key space fixed, and returns a
+/// structured result set ready to feed plotting or regression tooling.
+///
+/// `make` is called once per cell to obtain a fresh collection so state from a
+/// previous thread count can't leak into the next measurement.
+pub fn run_sweep<H, F>(
+    name: &str,
+    make: F,
+    keys: &Arc<Keys<<<H as Collection>::Handle as CollectionHandle>::Key>>,
+    thread_counts: &[usize],
+    workloads: &[(&str, Mix)],
+    params: &SweepParams,
+) -> SweepResults
+where
+    H: Collection,
+    F: Fn() -> Arc<H>,
+{
+    let mut results = SweepResults::default();
+
+    for (workload_name, mix) in workloads {
+        // Build the op list once per workload, seeded for reproducibility.
+        let operations = mix.to_ops_seeded(params.seed);
+        for &thread_count in thread_counts {
+            let config = SharedMapTestConfig {
+                thread_count,
+                total_ops: params.total_ops,
+                prefill: params.prefill,
+                operations: &operations,
+                keys_needed_per_thread: params.keys_needed_per_thread,
+                distribution: params.distribution,
+                record_latencies: params.record_latencies,
+                verify: params.verify,
+                affinity: params.affinity,
+                seed: params.seed,
+                flush_every: params.flush_every,
+            };
+            let m = run_shared_map_test(name, make(), &config, keys);
+            results.entries.push(SweepEntry {
+                name: name.to_string(),
+                workload: workload_name.to_string(),
+                thread_count: m.thread_count,
+                latency: m.latency,
+                p50: m.p50,
+                p99: m.p99,
+                p999: m.p999,
+                max: m.max,
+            });
+        }
+    }
+
+    results
+}
+
 pub(crate) trait MapAdapter<K, V> {
     fn insert(&mut self, key: K, value: V);
     fn get(&self, key: &K) -> Option<V>;
@@ -349,5 +970,12 @@ where
         name,
         latency: average_duration,
         thread_count: (prefill / 1000) as u64,
+        p50: average_duration,
+        p99: average_duration,
+        p999: average_duration,
+        max: average_duration,
+        affinity: Affinity::None,
+        prefill_nanos: 0.0,
+        flush_nanos: 0.0,
     }
 }